@@ -3,7 +3,7 @@ use parking_lot::Mutex;
 use portable_pty::{Child, CommandBuilder, MasterPty, NativePtySystem, PtySize, PtySystem};
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::HashMap,
+    collections::{HashMap, VecDeque},
     env,
     io::{Read, Write},
     path::{Path, PathBuf},
@@ -24,10 +24,69 @@ const TERM_PROGRAM_VERSION: &str = env!("CARGO_PKG_VERSION");
 const EMIT_INTERVAL: Duration = Duration::from_millis(16);
 const EMIT_MAX_BUFFER_BYTES: usize = 64 * 1024;
 
+/// Transport backing a [`TerminalSession`].
+///
+/// Local sessions are spawned through `portable_pty`'s `NativePtySystem`;
+/// remote sessions ride an interactive PTY channel on an SSH connection opened
+/// with `wezterm-ssh`. Both expose the same `MasterPty`/`Child` surface, so the
+/// reader, writer, resize and exit-watch machinery is shared — the variant only
+/// records where the session lives for the paths that must behave differently
+/// (notably signal delivery, which has no process group to target over SSH).
+pub enum SessionBackend {
+    Local,
+    Remote,
+}
+
+/// Default scrollback retained per session (~256 KiB of raw output).
+const SCROLLBACK_CAPACITY: usize = 256 * 1024;
+
+/// Bounded ring buffer of raw terminal output, kept for session replay.
+///
+/// Bytes are stored verbatim rather than as the lossy-decoded `String` the UI
+/// receives, so escape sequences are not mangled by decoding while buffered.
+/// Once the buffer reaches its capacity the oldest bytes are dropped first.
+pub struct Scrollback {
+    buffer: VecDeque<u8>,
+    capacity: usize,
+}
+
+impl Scrollback {
+    fn new(capacity: usize) -> Self {
+        Self {
+            buffer: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    fn push(&mut self, bytes: &[u8]) {
+        self.buffer.extend(bytes.iter().copied());
+        while self.buffer.len() > self.capacity {
+            self.buffer.pop_front();
+        }
+    }
+
+    /// Return up to `max_bytes` of the most recent output, realigned to a UTF-8
+    /// (and, where possible, line) boundary so a repaint never begins inside a
+    /// multi-byte character. Note that a line boundary is only a best-effort
+    /// guard against slicing an escape sequence — see [`scrollback_boundary`].
+    fn snapshot(&self, max_bytes: usize) -> String {
+        let bytes: Vec<u8> = self.buffer.iter().copied().collect();
+        let start = scrollback_boundary(&bytes, max_bytes);
+        String::from_utf8_lossy(&bytes[start..]).into_owned()
+    }
+}
+
 pub struct TerminalSession {
     pub master: Box<dyn MasterPty + Send>,
     pub writer: Arc<Mutex<Box<dyn Write + Send>>>,
     pub child: Arc<Mutex<Box<dyn Child + Send + Sync>>>,
+    pub backend: SessionBackend,
+    /// Process id of the child, captured at spawn. The shell is a session and
+    /// process-group leader (its pgid equals this pid), so signals can be
+    /// delivered to the whole group without locking the child handle.
+    pub pid: Option<u32>,
+    /// Raw output retained for replay when a view remounts or reattaches.
+    pub scrollback: Arc<Mutex<Scrollback>>,
 }
 
 pub struct TerminalState {
@@ -42,11 +101,32 @@ impl TerminalState {
     }
 }
 
+/// Connection details for an SSH-backed session.
+///
+/// Authentication is split into the two mechanisms the handshake can satisfy without a
+/// prompt: a password or an identity file. When both are absent the agent and
+/// the default identities in `~/.ssh` are used.
+#[derive(Deserialize)]
+pub struct SshTarget {
+    pub host: String,
+    pub port: Option<u16>,
+    pub user: Option<String>,
+    pub password: Option<String>,
+    pub identity_file: Option<String>,
+}
+
 #[derive(Deserialize)]
 pub struct CreateTerminalPayload {
     pub cols: u16,
     pub rows: u16,
     pub cwd: Option<String>,
+    pub ssh: Option<SshTarget>,
+    /// Environment overlay applied on top of the built-in defaults, letting a
+    /// caller scope a session to an agent, REPL or wrapper of its choosing.
+    pub env: Option<HashMap<String, String>>,
+    /// Command written to the PTY once, right after the child is confirmed
+    /// spawned, so a session can launch directly into a program.
+    pub initial_command: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -60,7 +140,6 @@ pub async fn create_terminal_session(
     state: State<'_, TerminalState>,
     window: Window,
 ) -> Result<CreateTerminalResponse, String> {
-    let pty_system = NativePtySystem::default();
     let size = PtySize {
         rows: payload.rows,
         cols: payload.cols,
@@ -68,51 +147,15 @@ pub async fn create_terminal_session(
         pixel_height: 0,
     };
 
-    let working_dir = resolve_working_directory(payload.cwd.as_deref())?;
-    let shell_path = resolve_shell();
-
-    let mut cmd = CommandBuilder::new(&shell_path);
-    if shell_accepts_login_flag(&shell_path) {
-        cmd.arg("-l");
-    }
-    if let Some(cwd) = working_dir.to_str() {
-        cmd.cwd(cwd);
-    }
-    apply_terminal_environment(&mut cmd, &shell_path);
-
-    let pair = pty_system.openpty(size).map_err(|e| e.to_string())?;
-    let child = pair
-        .slave
-        .spawn_command(cmd)
-        .map_err(|e| format!("Failed to spawn shell: {e}"))?;
-    drop(pair.slave);
-
-    let reader = pair
-        .master
-        .try_clone_reader()
-        .map_err(|e| format!("Failed to clone PTY reader: {e}"))?;
-    let writer = Arc::new(Mutex::new(
-        pair.master
-            .take_writer()
-            .map_err(|e| format!("Failed to take PTY writer: {e}"))?,
-    ));
-    let master = pair.master;
-    let child = Arc::new(Mutex::new(child));
-
-    let session_id = uuid::Uuid::new_v4().to_string();
-    state.sessions.lock().insert(
-        session_id.clone(),
-        TerminalSession {
-            master,
-            writer: writer.clone(),
-            child: child.clone(),
-        },
-    );
-
-    spawn_reader_thread(reader, window.clone(), session_id.clone());
-    spawn_exit_watcher(child, window, state.sessions.clone(), session_id.clone());
-
-    Ok(CreateTerminalResponse { session_id })
+    let spawned = spawn_session(
+        size,
+        payload.cwd.as_deref(),
+        payload.ssh.as_ref(),
+        payload.env.as_ref(),
+        payload.initial_command.as_deref(),
+    )
+    .await?;
+    Ok(register_session(spawned, state, window))
 }
 
 #[tauri::command]
@@ -161,6 +204,86 @@ pub async fn resize_terminal(
     Ok(())
 }
 
+#[tauri::command]
+pub async fn get_terminal_scrollback(
+    session_id: String,
+    max_bytes: Option<usize>,
+    state: State<'_, TerminalState>,
+) -> Result<String, String> {
+    let scrollback = {
+        let sessions = state.sessions.lock();
+        let Some(session) = sessions.get(&session_id) else {
+            return Err("Terminal session not found".to_string());
+        };
+        session.scrollback.clone()
+    };
+
+    let max_bytes = max_bytes.unwrap_or(SCROLLBACK_CAPACITY);
+    Ok(scrollback.lock().snapshot(max_bytes))
+}
+
+#[tauri::command]
+pub async fn send_terminal_signal(
+    session_id: String,
+    signal: String,
+    state: State<'_, TerminalState>,
+) -> Result<(), String> {
+    let (is_local, signal_context) = {
+        let sessions = state.sessions.lock();
+        let Some(session) = sessions.get(&session_id) else {
+            return Err("Terminal session not found".to_string());
+        };
+        let is_local = matches!(session.backend, SessionBackend::Local);
+        #[cfg(unix)]
+        let context = (session.master.as_raw_fd(), session.pid);
+        #[cfg(not(unix))]
+        let context = session.pid;
+        (is_local, context)
+    };
+
+    #[cfg(unix)]
+    {
+        let (master_fd, pid) = signal_context;
+
+        if !is_local {
+            return Err("Signals can only be delivered to local sessions".to_string());
+        }
+
+        let signum = parse_signal(&signal)?;
+
+        // Target the terminal's *foreground* process group, the one job control
+        // put on the tty with `tcsetpgrp`. That is what an interactive shell runs
+        // `sleep 100`/a build in, so `INT` interrupts the running program the way
+        // Ctrl-C does — the shell's own group ignores SIGINT at the prompt. Fall
+        // back to the session leader only when no foreground group is set up.
+        let pgid = master_fd
+            .and_then(|fd| {
+                let pgrp = unsafe { libc::tcgetpgrp(fd) };
+                (pgrp > 0).then_some(pgrp)
+            })
+            .or_else(|| pid.map(|pid| pid as libc::pid_t))
+            .ok_or_else(|| "Terminal process is no longer running".to_string())?;
+
+        // The exit watcher reaps and reports status if the signal tears the
+        // session down.
+        let result = unsafe { libc::killpg(pgid, signum) };
+        if result != 0 {
+            return Err(format!(
+                "Failed to deliver signal {signal}: {}",
+                std::io::Error::last_os_error()
+            ));
+        }
+
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = (signal_context, is_local, signal);
+        Err("Signal delivery is only supported on Unix".to_string())
+    }
+}
+
 #[tauri::command]
 pub async fn close_terminal(
     session_id: String,
@@ -175,6 +298,71 @@ pub async fn close_terminal(
     Ok(())
 }
 
+#[derive(Deserialize)]
+pub struct RunProcessPayload {
+    pub cwd: Option<String>,
+    pub program: String,
+    pub args: Option<Vec<String>>,
+    pub env: Option<HashMap<String, String>>,
+}
+
+#[derive(Serialize)]
+pub struct RunProcessResponse {
+    pub run_id: String,
+}
+
+/// Spawn a non-interactive child with piped stdout/stderr and stream its output.
+///
+/// Unlike [`create_terminal_session`], which fronts an interactive shell over a
+/// PTY, this runs a single program and keeps stdout and stderr as distinct
+/// streams. Output is emitted as `stdout`/`stderr` events on `process://{run_id}`
+/// and a final `exit` event carries the real exit code and optional signal, so
+/// callers driving build tools, linters or git get clean separated streams and a
+/// definitive status instead of interleaved terminal bytes.
+#[tauri::command]
+pub async fn run_process(
+    payload: RunProcessPayload,
+    window: Window,
+) -> Result<RunProcessResponse, String> {
+    use std::process::{Command, Stdio};
+
+    let working_dir = resolve_working_directory(payload.cwd.as_deref())?;
+
+    let mut cmd = Command::new(&payload.program);
+    cmd.current_dir(&working_dir);
+    if let Some(args) = &payload.args {
+        cmd.args(args);
+    }
+    if let Some(env) = &payload.env {
+        cmd.envs(env);
+    }
+    cmd.stdin(Stdio::null());
+    cmd.stdout(Stdio::piped());
+    cmd.stderr(Stdio::piped());
+
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| format!("Failed to spawn {}: {e}", payload.program))?;
+
+    let run_id = uuid::Uuid::new_v4().to_string();
+    let event_name = format!("process://{run_id}");
+
+    let stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| "Failed to capture stdout".to_string())?;
+    let stderr = child
+        .stderr
+        .take()
+        .ok_or_else(|| "Failed to capture stderr".to_string())?;
+
+    let stdout_pump = spawn_stream_pump(stdout, window.clone(), event_name.clone(), "stdout");
+    let stderr_pump = spawn_stream_pump(stderr, window.clone(), event_name.clone(), "stderr");
+    spawn_process_exit_watcher(child, window, event_name, vec![stdout_pump, stderr_pump]);
+
+    Ok(RunProcessResponse { run_id })
+}
+
 #[derive(Deserialize)]
 pub struct RestartTerminalPayload {
     pub session_id: String,
@@ -196,7 +384,6 @@ pub async fn restart_terminal_session(
         }
     }
 
-    let pty_system = NativePtySystem::default();
     let size = PtySize {
         rows: payload.rows,
         cols: payload.cols,
@@ -204,7 +391,71 @@ pub async fn restart_terminal_session(
         pixel_height: 0,
     };
 
-    let working_dir = resolve_working_directory(Some(&payload.cwd))?;
+    let spawned = spawn_session(size, Some(&payload.cwd), None, None, None).await?;
+    Ok(register_session(spawned, state, window))
+}
+
+#[derive(Deserialize)]
+pub struct ForceKillPayload {
+    pub session_id: Option<String>,
+    pub cwd: Option<String>,
+}
+
+#[tauri::command]
+pub async fn force_kill_terminal(
+    payload: ForceKillPayload,
+    state: State<'_, TerminalState>,
+) -> Result<(), String> {
+    let mut sessions = state.sessions.lock();
+
+    if let Some(session_id) = payload.session_id {
+        if let Some(session) = sessions.remove(&session_id) {
+            let _ = session.child.lock().kill();
+        }
+        return Ok(());
+    }
+
+    // Current API ignores cwd; keep behavior but avoid holding poisoned locks.
+    let _ = payload.cwd;
+
+    let ids: Vec<String> = sessions.keys().cloned().collect();
+    for id in ids {
+        if let Some(session) = sessions.remove(&id) {
+            let _ = session.child.lock().kill();
+        }
+    }
+
+    Ok(())
+}
+
+/// A freshly spawned session before it is registered in [`TerminalState`].
+struct SpawnedSession {
+    master: Box<dyn MasterPty + Send>,
+    reader: Box<dyn Read + Send>,
+    writer: Box<dyn Write + Send>,
+    child: Box<dyn Child + Send + Sync>,
+    backend: SessionBackend,
+}
+
+/// Open a session over the requested transport and return its raw handles.
+///
+/// When `ssh` is `Some` the session is backed by an interactive PTY channel on
+/// the remote host; otherwise a local shell is spawned through `portable_pty`.
+/// Both paths yield the same `MasterPty`/`Child` handles so the caller can drive
+/// them identically.
+async fn spawn_session(
+    size: PtySize,
+    cwd: Option<&str>,
+    ssh: Option<&SshTarget>,
+    env: Option<&HashMap<String, String>>,
+    initial_command: Option<&str>,
+) -> Result<SpawnedSession, String> {
+    if let Some(target) = ssh {
+        return open_remote_session(size, cwd, target, env, initial_command).await;
+    }
+
+    let pty_system = NativePtySystem::default();
+    let working_dir = resolve_working_directory(cwd)?;
     let shell_path = resolve_shell();
 
     let mut cmd = CommandBuilder::new(&shell_path);
@@ -214,9 +465,13 @@ pub async fn restart_terminal_session(
     if let Some(cwd) = working_dir.to_str() {
         cmd.cwd(cwd);
     }
-    apply_terminal_environment(&mut cmd, &shell_path);
+    apply_terminal_environment(&mut cmd, &shell_path, env);
 
     let pair = pty_system.openpty(size).map_err(|e| e.to_string())?;
+    // `NativePtySystem` spawns the child via `setsid` and marks the slave as its
+    // controlling terminal (the `TIOCSCTTY` setup alacritty performs), so the
+    // shell leads its own session/process group. That is what lets `killpg`
+    // deliver Ctrl-C-style signals and keeps job control and `tty` working.
     let child = pair
         .slave
         .spawn_command(cmd)
@@ -227,13 +482,172 @@ pub async fn restart_terminal_session(
         .master
         .try_clone_reader()
         .map_err(|e| format!("Failed to clone PTY reader: {e}"))?;
-    let writer = Arc::new(Mutex::new(
-        pair.master
-            .take_writer()
-            .map_err(|e| format!("Failed to take PTY writer: {e}"))?,
-    ));
-    let master = pair.master;
+    let mut writer = pair
+        .master
+        .take_writer()
+        .map_err(|e| format!("Failed to take PTY writer: {e}"))?;
+
+    // The child is spawned above, so it is safe to prime the session with the
+    // caller's startup command exactly once.
+    write_initial_command(&mut writer, initial_command)?;
+
+    Ok(SpawnedSession {
+        master: pair.master,
+        reader,
+        writer,
+        child,
+        backend: SessionBackend::Local,
+    })
+}
+
+/// Connect to `target` and open an interactive shell on a remote PTY channel.
+async fn open_remote_session(
+    size: PtySize,
+    cwd: Option<&str>,
+    target: &SshTarget,
+    env_overlay: Option<&HashMap<String, String>>,
+    initial_command: Option<&str>,
+) -> Result<SpawnedSession, String> {
+    use wezterm_ssh::{Config, Session, SessionEvent};
+
+    let mut config = Config::new();
+    config.add_default_config_files();
+
+    let mut opts = config.for_host(&target.host);
+    if let Some(user) = &target.user {
+        opts.insert("user".to_string(), user.clone());
+    }
+    if let Some(port) = target.port {
+        opts.insert("port".to_string(), port.to_string());
+    }
+    if let Some(identity) = &target.identity_file {
+        opts.insert("identityfile".to_string(), identity.clone());
+    }
+
+    let (session, events) =
+        Session::connect(opts).map_err(|e| format!("Failed to connect to {}: {e}", target.host))?;
+
+    // Drive the connection handshake to completion, answering the prompts the
+    // server raises with the credentials the caller supplied.
+    while let Ok(event) = events.recv().await {
+        match event {
+            SessionEvent::Banner(banner) => {
+                if let Some(banner) = banner {
+                    log::info!("SSH banner from {}: {banner}", target.host);
+                }
+            }
+            SessionEvent::HostVerify(verify) => {
+                // wezterm-ssh consults `known_hosts` itself and only raises this
+                // when the presented key is unknown or conflicts with a pinned
+                // one. The UI chose a *hostname*, not a *key*, and we have no way
+                // to prompt here, so refuse rather than blindly trusting a key the
+                // user never vetted — auto-accepting would open a silent MITM.
+                verify.answer(false).await.ok();
+                return Err(format!(
+                    "Host key verification failed for {}: {}",
+                    target.host, verify.message
+                ));
+            }
+            SessionEvent::Authenticate(auth) => {
+                let mut answers = Vec::with_capacity(auth.prompts.len());
+                // Only the first echoed prompt is treated as the username reply,
+                // and only when a user was actually supplied; later echoed prompts
+                // are keyboard-interactive challenges (2FA/OTP) we can't answer.
+                let mut username_answered = false;
+                for prompt in &auth.prompts {
+                    if prompt.echo {
+                        match &target.user {
+                            Some(user) if !username_answered => {
+                                answers.push(user.clone());
+                                username_answered = true;
+                            }
+                            _ => {
+                                return Err(format!(
+                                    "Server at {} issued an interactive prompt \"{}\" that cannot be answered automatically",
+                                    target.host,
+                                    prompt.prompt.trim()
+                                ));
+                            }
+                        }
+                    } else if let Some(password) = &target.password {
+                        answers.push(password.clone());
+                    } else {
+                        // Identity-file/agent auth supplied no password, yet the
+                        // server still wants an interactive secret we can't answer.
+                        return Err(format!(
+                            "Server at {} requested \"{}\" but no password was provided",
+                            target.host,
+                            prompt.prompt.trim()
+                        ));
+                    }
+                }
+                auth.answer(answers)
+                    .await
+                    .map_err(|e| format!("Authentication failed: {e}"))?;
+            }
+            SessionEvent::Error(err) => {
+                return Err(format!("SSH connection to {} failed: {err}", target.host));
+            }
+            SessionEvent::Authenticated => break,
+            _ => {}
+        }
+    }
+
+    let term = env::var("TERM").unwrap_or_else(|_| DEFAULT_TERM.to_string());
+    let mut env = HashMap::new();
+    env.insert("TERM".to_string(), term.clone());
+    // Overlay the caller's environment on top of the negotiated defaults.
+    if let Some(overlay) = env_overlay {
+        for (key, value) in overlay {
+            env.insert(key.clone(), value.clone());
+        }
+    }
+
+    let (pty, child) = session
+        .request_pty(&term, size, None, Some(env))
+        .await
+        .map_err(|e| format!("Failed to open remote PTY: {e}"))?;
+
+    let reader = pty
+        .try_clone_reader()
+        .map_err(|e| format!("Failed to clone remote PTY reader: {e}"))?;
+    let mut writer = pty
+        .take_writer()
+        .map_err(|e| format!("Failed to take remote PTY writer: {e}"))?;
+
+    // Unlike the local path's `cmd.cwd(...)`, an SSH PTY channel has no way to
+    // set the child's initial directory, so land the shell there by issuing a
+    // `cd` ahead of any startup command once the channel is live.
+    let startup = remote_startup_command(cwd, initial_command);
+    write_initial_command(&mut writer, startup.as_deref())?;
+
+    Ok(SpawnedSession {
+        master: Box::new(pty),
+        reader,
+        writer,
+        child: Box::new(child),
+        backend: SessionBackend::Remote,
+    })
+}
+
+/// Register a freshly spawned session and start its reader/exit watchers.
+fn register_session(
+    spawned: SpawnedSession,
+    state: State<'_, TerminalState>,
+    window: Window,
+) -> CreateTerminalResponse {
+    let SpawnedSession {
+        master,
+        reader,
+        writer,
+        child,
+        backend,
+    } = spawned;
+
+    let pid = child.process_id();
+    let writer = Arc::new(Mutex::new(writer));
     let child = Arc::new(Mutex::new(child));
+    let scrollback = Arc::new(Mutex::new(Scrollback::new(SCROLLBACK_CAPACITY)));
 
     let session_id = uuid::Uuid::new_v4().to_string();
     state.sessions.lock().insert(
@@ -242,49 +656,24 @@ pub async fn restart_terminal_session(
             master,
             writer: writer.clone(),
             child: child.clone(),
+            backend,
+            pid,
+            scrollback: scrollback.clone(),
         },
     );
 
-    spawn_reader_thread(reader, window.clone(), session_id.clone());
+    spawn_reader_thread(reader, window.clone(), session_id.clone(), scrollback);
     spawn_exit_watcher(child, window, state.sessions.clone(), session_id.clone());
 
-    Ok(CreateTerminalResponse { session_id })
-}
-
-#[derive(Deserialize)]
-pub struct ForceKillPayload {
-    pub session_id: Option<String>,
-    pub cwd: Option<String>,
-}
-
-#[tauri::command]
-pub async fn force_kill_terminal(
-    payload: ForceKillPayload,
-    state: State<'_, TerminalState>,
-) -> Result<(), String> {
-    let mut sessions = state.sessions.lock();
-
-    if let Some(session_id) = payload.session_id {
-        if let Some(session) = sessions.remove(&session_id) {
-            let _ = session.child.lock().kill();
-        }
-        return Ok(());
-    }
-
-    // Current API ignores cwd; keep behavior but avoid holding poisoned locks.
-    let _ = payload.cwd;
-
-    let ids: Vec<String> = sessions.keys().cloned().collect();
-    for id in ids {
-        if let Some(session) = sessions.remove(&id) {
-            let _ = session.child.lock().kill();
-        }
-    }
-
-    Ok(())
+    CreateTerminalResponse { session_id }
 }
 
-fn spawn_reader_thread(reader: Box<dyn Read + Send>, window: Window, session_id: String) {
+fn spawn_reader_thread(
+    reader: Box<dyn Read + Send>,
+    window: Window,
+    session_id: String,
+    scrollback: Arc<Mutex<Scrollback>>,
+) {
     thread::spawn(move || {
         use std::sync::mpsc;
 
@@ -370,6 +759,8 @@ fn spawn_reader_thread(reader: Box<dyn Read + Send>, window: Window, session_id:
         loop {
             match rx.recv_timeout(EMIT_INTERVAL) {
                 Ok(bytes) => {
+                    // Retain the raw bytes for replay before decoding for emit.
+                    scrollback.lock().push(&bytes);
                     pending_bytes.extend_from_slice(&bytes);
                     decode_pending(&mut pending_bytes, &mut pending);
 
@@ -411,7 +802,17 @@ fn spawn_exit_watcher(
     session_id: String,
 ) {
     thread::spawn(move || {
-        let status = { child.lock().wait() };
+        // Poll for exit rather than holding the child lock on a blocking `wait`,
+        // so `send_terminal_signal`/`close_terminal` can still reach the child.
+        // This is the reap path: whether the child exits on its own or is felled
+        // by a delivered signal, the status is observed here.
+        let status = loop {
+            match child.lock().try_wait() {
+                Ok(Some(status)) => break Ok(status),
+                Ok(None) => thread::sleep(Duration::from_millis(50)),
+                Err(err) => break Err(err),
+            }
+        };
 
         let (exit_code, signal) = match status {
             Ok(status) => (
@@ -436,6 +837,201 @@ fn spawn_exit_watcher(
     });
 }
 
+/// Pump a piped child stream, emitting chunks tagged with `stream_type`.
+///
+/// This mirrors [`spawn_reader_thread`]'s throttling — coalescing reads up to
+/// [`EMIT_INTERVAL`] and flushing early past [`EMIT_MAX_BUFFER_BYTES`] — but keeps
+/// stdout and stderr on their own `stream_type` so the frontend can render them
+/// apart. Bytes that split a multi-byte UTF-8 character at a chunk boundary are
+/// carried over rather than lossily replaced.
+fn spawn_stream_pump(
+    reader: impl Read + Send + 'static,
+    window: Window,
+    event_name: String,
+    stream_type: &'static str,
+) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        use std::sync::mpsc;
+
+        let (tx, rx) = mpsc::channel::<Vec<u8>>();
+
+        let reader_handle = thread::spawn(move || {
+            let mut reader = reader;
+            let mut buffer = [0u8; 16384];
+            loop {
+                match reader.read(&mut buffer) {
+                    Ok(0) => break,
+                    Ok(n) => {
+                        if tx.send(buffer[..n].to_vec()).is_err() {
+                            break;
+                        }
+                    }
+                    Err(_) => break,
+                }
+            }
+        });
+
+        let mut pending_bytes: Vec<u8> = Vec::new();
+
+        let flush = |pending_bytes: &mut Vec<u8>, drain_all: bool| -> bool {
+            // Keep a trailing partial UTF-8 sequence buffered unless we are
+            // draining the final remainder after the stream closed.
+            let mut take = pending_bytes.len();
+            if !drain_all {
+                let mut checked = 0;
+                while let Err(error) = std::str::from_utf8(&pending_bytes[checked..]) {
+                    match error.error_len() {
+                        // Invalid bytes are emitted (lossily replaced), not held.
+                        Some(len) => checked += error.valid_up_to() + len,
+                        None => {
+                            take = checked + error.valid_up_to();
+                            break;
+                        }
+                    }
+                }
+            }
+            if take == 0 {
+                return true;
+            }
+
+            let chunk: Vec<u8> = pending_bytes.drain(..take).collect();
+            let text = String::from_utf8_lossy(&chunk).into_owned();
+            let payload = serde_json::json!({ "type": stream_type, "data": text });
+
+            match window.emit(&event_name, payload) {
+                Ok(_) => true,
+                Err(error) => {
+                    error!("Failed to emit {stream_type}: {error}");
+                    false
+                }
+            }
+        };
+
+        loop {
+            match rx.recv_timeout(EMIT_INTERVAL) {
+                Ok(bytes) => {
+                    pending_bytes.extend_from_slice(&bytes);
+                    if pending_bytes.len() >= EMIT_MAX_BUFFER_BYTES && !flush(&mut pending_bytes, false)
+                    {
+                        break;
+                    }
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => {
+                    if !flush(&mut pending_bytes, false) {
+                        break;
+                    }
+                }
+                Err(mpsc::RecvTimeoutError::Disconnected) => {
+                    let _ = flush(&mut pending_bytes, true);
+                    break;
+                }
+            }
+        }
+
+        let _ = reader_handle.join();
+    });
+}
+
+/// Reap a non-interactive child and report its final status on `event_name`.
+///
+/// The `process://{run_id}` counterpart of [`spawn_exit_watcher`]: it blocks on
+/// the child, then emits a single `exit` event carrying the real exit code and,
+/// on Unix, the terminating signal when one felled the process.
+///
+/// `pumps` are the stdout/stderr [`spawn_stream_pump`] handles. They are joined
+/// to EOF *before* `exit` is emitted so every `stdout`/`stderr` event is
+/// delivered first — a consumer that finalizes on `exit` never loses trailing
+/// output.
+fn spawn_process_exit_watcher(
+    mut child: std::process::Child,
+    window: Window,
+    event_name: String,
+    pumps: Vec<thread::JoinHandle<()>>,
+) {
+    thread::spawn(move || {
+        let (exit_code, signal) = match child.wait() {
+            Ok(status) => {
+                #[cfg(unix)]
+                let signal = {
+                    use std::os::unix::process::ExitStatusExt;
+                    status.signal().map(|sig| sig.to_string())
+                };
+                #[cfg(not(unix))]
+                let signal: Option<String> = None;
+
+                (status.code().unwrap_or(-1), signal)
+            }
+            Err(err) => {
+                error!("Failed to wait for process exit: {err}");
+                (-1, Some("Process crashed".to_string()))
+            }
+        };
+
+        // Drain both streams to EOF so no output can race past the exit event.
+        for pump in pumps {
+            let _ = pump.join();
+        }
+
+        let payload = serde_json::json!({
+            "type": "exit",
+            "exitCode": exit_code,
+            "signal": signal
+        });
+        let _ = window.emit(&event_name, payload);
+    });
+}
+
+/// Map a signal name (with or without the `SIG` prefix) to its number.
+#[cfg(unix)]
+fn parse_signal(name: &str) -> Result<libc::c_int, String> {
+    let normalized = name.trim().to_uppercase();
+    let normalized = normalized.strip_prefix("SIG").unwrap_or(&normalized);
+
+    let signum = match normalized {
+        "INT" => libc::SIGINT,
+        "TERM" => libc::SIGTERM,
+        "HUP" => libc::SIGHUP,
+        "QUIT" => libc::SIGQUIT,
+        "KILL" => libc::SIGKILL,
+        "USR1" => libc::SIGUSR1,
+        other => return Err(format!("Unsupported signal: {other}")),
+    };
+
+    Ok(signum)
+}
+
+/// Pick a replay start offset within `bytes` that keeps at most `max_bytes` of
+/// trailing output while avoiding a cut through a multi-byte character or an
+/// in-flight escape sequence.
+///
+/// The offset is advanced past any leading UTF-8 continuation bytes, then, when
+/// the retained window does not already begin a fresh line, nudged forward to
+/// just after the next newline. A line boundary is a cheap heuristic, not a
+/// guarantee: CSI/OSC sequences are not newline-delimited, so replay can still
+/// resume mid-sequence. The UTF-8 realignment is exact; the line realignment
+/// only reduces the odds of slicing an escape sequence, at the cost of at most
+/// one partial leading line.
+fn scrollback_boundary(bytes: &[u8], max_bytes: usize) -> usize {
+    // When everything fits, the window starts at the buffer's front, which ring
+    // eviction may have cut mid-character; it is realigned below all the same.
+    let mut start = bytes.len().saturating_sub(max_bytes);
+
+    // Never begin on a UTF-8 continuation byte (0b10xxxxxx).
+    while start < bytes.len() && bytes[start] & 0xC0 == 0x80 {
+        start += 1;
+    }
+
+    // Realign to a line boundary unless the window already starts one.
+    let at_line_start = start == 0 || bytes.get(start - 1) == Some(&b'\n');
+    if !at_line_start {
+        if let Some(nl) = bytes[start..].iter().position(|&b| b == b'\n') {
+            start += nl + 1;
+        }
+    }
+
+    start
+}
+
 fn resolve_shell() -> String {
     env::var("SHELL")
         .ok()
@@ -477,7 +1073,49 @@ fn resolve_working_directory(input: Option<&str>) -> Result<PathBuf, String> {
     Ok(path)
 }
 
-fn apply_terminal_environment(cmd: &mut CommandBuilder, shell_path: &str) {
+/// Build the line fed to a remote shell to place it in `cwd` and run the
+/// caller's `initial_command`, if either is requested.
+///
+/// The directory is issued as a leading `cd` (single-quoted so paths with
+/// spaces survive) because an SSH PTY channel, unlike the local spawn, cannot
+/// set the child's working directory directly. Returns `None` when there is
+/// nothing to send, so the common bare-shell case writes no startup line.
+fn remote_startup_command(cwd: Option<&str>, initial_command: Option<&str>) -> Option<String> {
+    let mut parts: Vec<String> = Vec::new();
+
+    if let Some(dir) = cwd.map(str::trim).filter(|d| !d.is_empty()) {
+        parts.push(format!("cd '{}'", dir.replace('\'', "'\\''")));
+    }
+    if let Some(command) = initial_command.map(str::trim).filter(|c| !c.is_empty()) {
+        parts.push(command.to_string());
+    }
+
+    if parts.is_empty() {
+        None
+    } else {
+        Some(parts.join(" && "))
+    }
+}
+
+/// Write the caller's startup command to a freshly spawned session's PTY.
+///
+/// A trailing newline is appended so the shell executes the line immediately.
+/// No-op when no command was supplied, keeping the common case untouched.
+fn write_initial_command(writer: &mut (impl Write + ?Sized), command: Option<&str>) -> Result<(), String> {
+    let Some(command) = command.map(str::trim).filter(|c| !c.is_empty()) else {
+        return Ok(());
+    };
+
+    writer
+        .write_all(format!("{command}\n").as_bytes())
+        .map_err(|e| format!("Failed to write initial command: {e}"))
+}
+
+fn apply_terminal_environment(
+    cmd: &mut CommandBuilder,
+    shell_path: &str,
+    overlay: Option<&HashMap<String, String>>,
+) {
     cmd.env(
         "TERM",
         env::var("TERM").unwrap_or_else(|_| DEFAULT_TERM.to_string()),
@@ -498,4 +1136,11 @@ fn apply_terminal_environment(cmd: &mut CommandBuilder, shell_path: &str) {
     cmd.env("TERM_PROGRAM_VERSION", TERM_PROGRAM_VERSION);
     cmd.env("OPENCHAMBER_DESKTOP", "1");
     cmd.env("SHELL", shell_path);
+
+    // Apply the caller's overlay last so it can override any default above.
+    if let Some(overlay) = overlay {
+        for (key, value) in overlay {
+            cmd.env(key, value);
+        }
+    }
 }